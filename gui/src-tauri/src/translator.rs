@@ -1,8 +1,15 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Listener, Manager};
 use std::time::Duration;
-use std::io::Write;
+use std::io::{Read, Write};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rand::Rng;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TranslatorConfig {
@@ -17,11 +24,304 @@ pub struct TranslatorConfig {
     pub stream: bool,
     pub threads: usize,
     pub batch_size: usize,
-    pub delay: f64,
     pub last_file: String,
+    pub cache_enabled: bool,
+    pub cache_path: String,
+    pub max_prompt_tokens: usize,
+    pub max_retries: u32,
+    pub request_timeout_ms: u64,
+    pub requests_per_second: f64,
+    pub resume: bool,
+    pub save_interval_secs: u64,
+    pub max_repair_attempts: u32,
+}
+
+/// Number of batches between checkpoint flushes, in addition to the
+/// time-based `save_interval_secs` trigger.
+const CHECKPOINT_BATCH_INTERVAL: usize = 5;
+
+/// Sidecar recording which line indices of `file_path` are already
+/// translated, so a resumed run can skip straight past them.
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct Checkpoint {
+    file_path: String,
+    completed_indices: Vec<usize>,
+}
+
+fn derive_output_path(file_path: &str) -> String {
+    format!("{}.tran.txt", file_path)
+}
+
+fn derive_checkpoint_path(file_path: &str) -> String {
+    format!("{}.checkpoint.json", file_path)
+}
+
+/// Loads the checkpoint for `file_path`, if one exists and matches.
+fn load_checkpoint(checkpoint_path: &str, file_path: &str) -> Option<Checkpoint> {
+    let json = std::fs::read_to_string(checkpoint_path).ok()?;
+    let checkpoint: Checkpoint = serde_json::from_str(&json).ok()?;
+    if checkpoint.file_path == file_path {
+        Some(checkpoint)
+    } else {
+        None
+    }
+}
+
+/// Flushes the in-progress output lines and the set of completed indices to
+/// disk, so a crash or an explicit stop loses at most one checkpoint interval
+/// of work instead of the whole file.
+fn save_checkpoint(
+    file_path: &str,
+    output_path: &str,
+    checkpoint_path: &str,
+    lines: &[String],
+    completed_indices: &std::collections::HashSet<usize>,
+) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(output_path)?;
+    for line in lines {
+        writeln!(file, "{}", line)?;
+    }
+
+    let checkpoint = Checkpoint {
+        file_path: file_path.to_string(),
+        completed_indices: completed_indices.iter().copied().collect(),
+    };
+    let json = serde_json::to_string(&checkpoint)?;
+    std::fs::write(checkpoint_path, json)?;
+    Ok(())
+}
+
+/// The state a resumable run registers with `TranslatorState` so
+/// `stop_translation` can flush a checkpoint immediately instead of waiting
+/// for the next periodic save.
+struct ActiveCheckpoint {
+    file_path: String,
+    output_path: String,
+    checkpoint_path: String,
+    output_mutex: Arc<Mutex<Vec<String>>>,
+    completed_indices: Arc<Mutex<std::collections::HashSet<usize>>>,
+}
+
+/// Shared token-bucket limiter so every worker thread draws from one global
+/// requests-per-second ceiling instead of each sleeping independently, which
+/// let thread count alone determine burst rate.
+struct RateLimiter {
+    tokens: Mutex<f64>,
+    max_tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Mutex<std::time::Instant>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_second: f64) -> Self {
+        let rate = requests_per_second.max(0.001);
+        // Capacity is at least one whole token, so rates below 1/s still
+        // admit a request once a full token has refilled.
+        let capacity = rate.max(1.0);
+        Self {
+            tokens: Mutex::new(capacity),
+            max_tokens: capacity,
+            refill_per_sec: rate,
+            last_refill: Mutex::new(std::time::Instant::now()),
+        }
+    }
+
+    async fn acquire(&self) {
+        loop {
+            {
+                let mut tokens = self.tokens.lock().unwrap();
+                let mut last = self.last_refill.lock().unwrap();
+                let now = std::time::Instant::now();
+                let elapsed = now.duration_since(*last).as_secs_f64();
+                *last = now;
+                *tokens = (*tokens + elapsed * self.refill_per_sec).min(self.max_tokens);
+
+                if *tokens >= 1.0 {
+                    *tokens -= 1.0;
+                    return;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+}
+
+/// Cheap bytes/4 token estimate (UTF-8 length, so non-ASCII text counts
+/// several bytes per character). Good enough to keep prompts under budget
+/// without pulling in a real tokenizer.
+fn estimate_tokens(text: &str) -> usize {
+    (text.len() / 4).max(1)
+}
+
+/// Accumulates work items into batches bounded by both an estimated token
+/// budget and `batch_size` as a hard line-count ceiling, so a handful of very
+/// long lines can't blow out a prompt the way fixed-size chunking did.
+fn chunk_by_tokens(
+    items: &[(usize, String)],
+    batch_size: usize,
+    max_prompt_tokens: usize,
+) -> Vec<Vec<(usize, String)>> {
+    let mut batches = Vec::new();
+    let mut buffer: Vec<(usize, String)> = Vec::new();
+    let mut buffer_tokens = 0usize;
+
+    for item in items {
+        let item_tokens = estimate_tokens(&item.1);
+        let would_overflow = buffer_tokens + item_tokens > max_prompt_tokens;
+
+        if !buffer.is_empty() && (would_overflow || buffer.len() >= batch_size) {
+            batches.push(std::mem::take(&mut buffer));
+            buffer_tokens = 0;
+        }
+
+        buffer.push(item.clone());
+        buffer_tokens += item_tokens;
+    }
+
+    if !buffer.is_empty() {
+        batches.push(buffer);
+    }
+
+    batches
+}
+
+/// Translation-memory cache: maps a hash of a normalized source segment to its
+/// translated text, so reruns over mostly-unchanged files skip the API entirely.
+type TmCache = Arc<Mutex<HashMap<String, String>>>;
+
+/// Normalizes a source segment before hashing so trivial whitespace drift
+/// between patches doesn't cause spurious cache misses: leading/trailing
+/// whitespace is trimmed and runs of interior whitespace are collapsed to a
+/// single space.
+fn normalize_segment(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Segment hashes currently being sent to the API by some thread this run,
+/// so a second thread that hits the same new segment waits for the first
+/// thread's result instead of issuing a duplicate request.
+type InFlightSet = Arc<Mutex<std::collections::HashSet<String>>>;
+
+fn hash_segment(text: &str) -> String {
+    let normalized = normalize_segment(text);
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    normalized.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+/// Loads the gzip-compressed TM cache from disk. Missing or corrupt files are
+/// treated as an empty cache rather than a hard error.
+fn load_tm_cache(cache_path: &str) -> HashMap<String, String> {
+    let file = match std::fs::File::open(cache_path) {
+        Ok(f) => f,
+        Err(_) => return HashMap::new(),
+    };
+    let mut decoder = GzDecoder::new(file);
+    let mut json = String::new();
+    if decoder.read_to_string(&mut json).is_err() {
+        return HashMap::new();
+    }
+    serde_json::from_str(&json).unwrap_or_default()
 }
 
-#[derive(Clone, Serialize)]
+/// Persists the TM cache to disk as gzip-compressed JSON.
+fn save_tm_cache(cache_path: &str, cache: &HashMap<String, String>) -> std::io::Result<()> {
+    let json = serde_json::to_string(cache)?;
+    let file = std::fs::File::create(cache_path)?;
+    let mut encoder = GzEncoder::new(file, Compression::default());
+    encoder.write_all(json.as_bytes())?;
+    encoder.finish()?;
+    Ok(())
+}
+
+/// Counters a benchmark run accumulates across every batch of a job, so
+/// `run_benchmark` can report them without `call_api_translate` itself
+/// needing to know it's being benchmarked.
+struct BenchmarkStats {
+    tokens_used: std::sync::atomic::AtomicU64,
+    missing_ids: std::sync::atomic::AtomicUsize,
+    malformed_ids: std::sync::atomic::AtomicUsize,
+    /// The job's `expected_format`, checked against every reply line's
+    /// `:::`-delimited field count (see `line_matches_format`).
+    expected_format: String,
+}
+
+impl BenchmarkStats {
+    fn new(expected_format: String) -> Self {
+        Self {
+            tokens_used: std::sync::atomic::AtomicU64::new(0),
+            missing_ids: std::sync::atomic::AtomicUsize::new(0),
+            malformed_ids: std::sync::atomic::AtomicUsize::new(0),
+            expected_format,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct BenchmarkJob {
+    name: String,
+    input_sample: String,
+    model: String,
+    temperature: f64,
+    threads: usize,
+    batch_size: usize,
+    /// Describes the line format a correct reply should follow (e.g.
+    /// `"ID:::Text"`), checked by field count against `expected_format`'s own
+    /// `:::`-delimited field count. Lines that don't split on `:::` at all
+    /// are always malformed regardless of this value.
+    #[serde(default = "default_expected_format")]
+    expected_format: String,
+}
+
+fn default_expected_format() -> String {
+    "ID:::Text".to_string()
+}
+
+/// Checks a reply line against a job's `expected_format` by comparing
+/// `:::`-delimited field counts, e.g. `"ID:::Text"` expects 2 fields. Lets a
+/// workload describe a stricter format (say `"ID:::Text:::Context"`) and have
+/// replies that are missing or adding fields counted as malformed even though
+/// they still split once on `:::`.
+fn line_matches_format(line: &str, expected_format: &str) -> bool {
+    line.split(":::").count() == expected_format.split(":::").count()
+}
+
+#[derive(Debug, Deserialize, Clone)]
+struct Workload {
+    jobs: Vec<BenchmarkJob>,
+    #[serde(default)]
+    results_file: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThreadTiming {
+    thread_id: usize,
+    elapsed_secs: f64,
+    lines: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BenchmarkResult {
+    job_name: String,
+    model: String,
+    threads: usize,
+    batch_size: usize,
+    total_lines: usize,
+    elapsed_secs: f64,
+    lines_per_sec: f64,
+    total_tokens: u64,
+    missing_ids: usize,
+    malformed_ids: usize,
+    expected_format: String,
+    thread_timings: Vec<ThreadTiming>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BenchmarkReport {
+    results: Vec<BenchmarkResult>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 struct ProgressEvent {
     thread_id: usize,
     current: usize,
@@ -32,23 +332,49 @@ struct ProgressEvent {
 
 pub struct TranslatorState {
     pub stop_flag: Arc<Mutex<bool>>,
+    active_checkpoint: Mutex<Option<ActiveCheckpoint>>,
+    /// The config of the most recent run started from the GUI or loaded by
+    /// the control socket's `start <file> <config_path>` command; a bare
+    /// `start <file>` on the socket reuses it.
+    last_config: Mutex<Option<TranslatorConfig>>,
+    running: Arc<Mutex<bool>>,
 }
 
 impl TranslatorState {
     pub fn new() -> Self {
         Self {
             stop_flag: Arc::new(Mutex::new(false)),
+            active_checkpoint: Mutex::new(None),
+            last_config: Mutex::new(None),
+            running: Arc::new(Mutex::new(false)),
         }
     }
 }
 
-#[tauri::command]
-pub async fn stop_translation(state: tauri::State<'_, TranslatorState>) -> Result<(), String> {
+fn stop_translation_core(state: &TranslatorState) -> Result<(), String> {
     let mut stop = state.stop_flag.lock().map_err(|e| e.to_string())?;
     *stop = true;
+
+    if let Some(active) = state.active_checkpoint.lock().map_err(|e| e.to_string())?.as_ref() {
+        let lines = active.output_mutex.lock().map_err(|e| e.to_string())?;
+        let completed = active.completed_indices.lock().map_err(|e| e.to_string())?;
+        save_checkpoint(
+            &active.file_path,
+            &active.output_path,
+            &active.checkpoint_path,
+            &lines,
+            &completed,
+        ).map_err(|e| e.to_string())?;
+    }
+
     Ok(())
 }
 
+#[tauri::command]
+pub async fn stop_translation(state: tauri::State<'_, TranslatorState>) -> Result<(), String> {
+    stop_translation_core(&state)
+}
+
 #[tauri::command]
 pub async fn fetch_models(base_url: String, api_key: String) -> Result<Vec<String>, String> {
     let client = reqwest::Client::new();
@@ -95,6 +421,30 @@ pub async fn start_translation(
     state: tauri::State<'_, TranslatorState>,
     config: TranslatorConfig,
     file_path: String,
+) -> Result<(), String> {
+    *state.last_config.lock().map_err(|e| e.to_string())? = Some(config.clone());
+    run_translation_core(app, &state, config, file_path).await
+}
+
+/// The actual translation run, shared by the `start_translation` command and
+/// the headless control-socket `start <file>` command.
+async fn run_translation_core(
+    app: AppHandle,
+    state: &TranslatorState,
+    config: TranslatorConfig,
+    file_path: String,
+) -> Result<(), String> {
+    *state.running.lock().map_err(|e| e.to_string())? = true;
+    let result = run_translation_inner(&app, state, config, file_path).await;
+    *state.running.lock().map_err(|e| e.to_string())? = false;
+    result
+}
+
+async fn run_translation_inner(
+    app: &AppHandle,
+    state: &TranslatorState,
+    config: TranslatorConfig,
+    file_path: String,
 ) -> Result<(), String> {
     // Reset stop flag
     {
@@ -104,33 +454,88 @@ pub async fn start_translation(
 
     let content = std::fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
     let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-    
+
+    let output_path = derive_output_path(&file_path);
+    let checkpoint_path = derive_checkpoint_path(&file_path);
+
+    // When resuming, seed the output buffer from the partially-translated
+    // output of a previous run (if any) instead of the untranslated source,
+    // and recover which indices were already completed.
+    let existing_checkpoint = if config.resume {
+        load_checkpoint(&checkpoint_path, &file_path)
+    } else {
+        None
+    };
+
+    let mut output_lines = lines.clone();
+    let mut completed_indices: std::collections::HashSet<usize> = std::collections::HashSet::new();
+
+    if let Some(checkpoint) = &existing_checkpoint {
+        completed_indices = checkpoint.completed_indices.iter().copied().collect();
+        if let Ok(prev_output) = std::fs::read_to_string(&output_path) {
+            let prev_lines: Vec<String> = prev_output.lines().map(|s| s.to_string()).collect();
+            for &idx in &completed_indices {
+                if let Some(line) = prev_lines.get(idx) {
+                    if idx < output_lines.len() {
+                        output_lines[idx] = line.clone();
+                    }
+                }
+            }
+        }
+    }
+
     // Parse lines to find ID:::Text or just Text
     // We need to keep track of original indices to reconstruct the file
     let mut work_items = Vec::new();
-    let output_lines = lines.clone();
-    
+
     // Check for header (0:::)
     let start_idx = if !lines.is_empty() && lines[0].starts_with("0:::") { 1 } else { 0 };
-    
+
     for (i, line) in lines.iter().enumerate().skip(start_idx) {
+        if completed_indices.contains(&i) {
+            continue;
+        }
         work_items.push((i, line.clone()));
     }
 
     let total_items = work_items.len();
     let num_threads = config.threads.max(1);
-    let chunk_size = (total_items as f64 / num_threads as f64).ceil() as usize;
-    
+    let chunk_size = (total_items as f64 / num_threads as f64).ceil().max(1.0) as usize;
+
     let chunks: Vec<Vec<(usize, String)>> = work_items.chunks(chunk_size).map(|c| c.to_vec()).collect();
     
     let stop_flag = state.stop_flag.clone();
     let config = Arc::new(config);
     let output_mutex = Arc::new(Mutex::new(output_lines));
-    
-    // Create a semaphore to limit concurrent requests if needed, 
+
+    // In-memory TM layer: dedupes identical segments across threads within
+    // this run, and is seeded from the on-disk cache so unchanged lines from
+    // previous runs never hit the API at all.
+    let tm_cache: TmCache = Arc::new(Mutex::new(if config.cache_enabled {
+        load_tm_cache(&config.cache_path)
+    } else {
+        HashMap::new()
+    }));
+    let in_flight: InFlightSet = Arc::new(Mutex::new(std::collections::HashSet::new()));
+
+    let rate_limiter = Arc::new(RateLimiter::new(config.requests_per_second));
+
+    let completed_indices = Arc::new(Mutex::new(completed_indices));
+    let batches_since_save = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let last_save = Arc::new(Mutex::new(std::time::Instant::now()));
+
+    *state.active_checkpoint.lock().map_err(|e| e.to_string())? = Some(ActiveCheckpoint {
+        file_path: file_path.clone(),
+        output_path: output_path.clone(),
+        checkpoint_path: checkpoint_path.clone(),
+        output_mutex: output_mutex.clone(),
+        completed_indices: completed_indices.clone(),
+    });
+
+    // Create a semaphore to limit concurrent requests if needed,
     // but here we use threads as the limit.
     // Actually, we will spawn tasks.
-    
+
     let mut handles = vec![];
 
     for (thread_id, chunk) in chunks.into_iter().enumerate() {
@@ -138,7 +543,16 @@ pub async fn start_translation(
         let stop_flag = stop_flag.clone();
         let app_handle = app.clone();
         let output_mutex = output_mutex.clone();
-        
+        let tm_cache = tm_cache.clone();
+        let in_flight = in_flight.clone();
+        let rate_limiter = rate_limiter.clone();
+        let completed_indices = completed_indices.clone();
+        let batches_since_save = batches_since_save.clone();
+        let last_save = last_save.clone();
+        let file_path = file_path.clone();
+        let output_path = output_path.clone();
+        let checkpoint_path = checkpoint_path.clone();
+
         let handle = tokio::spawn(async move {
             let thread_id = thread_id + 1;
             let total_in_chunk = chunk.len();
@@ -153,10 +567,15 @@ pub async fn start_translation(
                 append: false,
             });
 
-            let client = reqwest::Client::new();
+            let client = reqwest::ClientBuilder::new()
+                .timeout(Duration::from_millis(config.request_timeout_ms))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new());
             let mut processed = 0;
 
-            for batch in chunk.chunks(config.batch_size) {
+            let batches = chunk_by_tokens(&chunk, config.batch_size, config.max_prompt_tokens);
+
+            for batch in &batches {
                 if *stop_flag.lock().unwrap() {
                     let _ = app_handle.emit("progress", ProgressEvent {
                         thread_id,
@@ -174,13 +593,19 @@ pub async fn start_translation(
                 
                 // Call API
                 let translated = call_api_translate(
-                    &client, 
-                    &config, 
-                    &batch_lines, 
-                    &app_handle, 
-                    thread_id,
-                    processed,
-                    total_in_chunk
+                    &client,
+                    &config,
+                    &batch_lines,
+                    &ProgressContext {
+                        app: &app_handle,
+                        thread_id,
+                        current_processed: processed,
+                        total_in_chunk,
+                    },
+                    &tm_cache,
+                    &in_flight,
+                    &rate_limiter,
+                    None,
                 ).await;
 
                 // Update output
@@ -189,11 +614,25 @@ pub async fn start_translation(
                     for (idx, text) in batch_indices.iter().zip(translated.iter()) {
                         out[*idx] = text.clone();
                     }
-                    // Save temp progress (optional, maybe too heavy to do every batch if many threads)
-                    // For now, let's skip saving to file every batch to avoid lock contention, 
-                    // or do it less frequently.
+                    let mut completed = completed_indices.lock().unwrap();
+                    completed.extend(batch_indices.iter().copied());
                 }
-                
+
+                // Checkpoint periodically (every N batches or every
+                // save_interval_secs) so a crash or stop loses at most one
+                // interval of progress instead of the whole file.
+                let batch_count = batches_since_save.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                let elapsed_since_save = last_save.lock().unwrap().elapsed().as_secs();
+                if batch_count >= CHECKPOINT_BATCH_INTERVAL || elapsed_since_save >= config.save_interval_secs {
+                    let lines = output_mutex.lock().unwrap();
+                    let completed = completed_indices.lock().unwrap();
+                    let _ = save_checkpoint(&file_path, &output_path, &checkpoint_path, &lines, &completed);
+                    drop(lines);
+                    drop(completed);
+                    batches_since_save.store(0, std::sync::atomic::Ordering::SeqCst);
+                    *last_save.lock().unwrap() = std::time::Instant::now();
+                }
+
                 processed += batch.len();
                 let _ = app_handle.emit("progress", ProgressEvent {
                     thread_id,
@@ -202,9 +641,6 @@ pub async fn start_translation(
                     message: "".to_string(), // Clear message or keep last
                     append: false,
                 });
-                
-                // Delay
-                tokio::time::sleep(Duration::from_secs_f64(config.delay)).await;
             }
             
             let _ = app_handle.emit("progress", ProgressEvent {
@@ -226,29 +662,573 @@ pub async fn start_translation(
     // Save final result
     if !*stop_flag.lock().unwrap() {
         let final_lines = output_mutex.lock().unwrap();
-        let output_path = "tran.txt"; // Or derive from input path
-        let mut file = std::fs::File::create(output_path).map_err(|e| e.to_string())?;
+        let mut file = std::fs::File::create(&output_path).map_err(|e| e.to_string())?;
         for line in final_lines.iter() {
             writeln!(file, "{}", line).map_err(|e| e.to_string())?;
         }
+        drop(final_lines);
+        // The file finished cleanly; drop the checkpoint so a future run
+        // doesn't think there's progress to resume.
+        let _ = std::fs::remove_file(&checkpoint_path);
+    }
+
+    if config.cache_enabled {
+        let cache = tm_cache.lock().unwrap();
+        save_tm_cache(&config.cache_path, &cache).map_err(|e| e.to_string())?;
     }
 
+    *state.active_checkpoint.lock().map_err(|e| e.to_string())? = None;
+
     Ok(())
 }
 
-async fn call_api_translate(
-    client: &reqwest::Client,
-    config: &TranslatorConfig,
-    lines: &[String],
+/// Runs every job in a workload file through `call_api_translate` and
+/// reports throughput/quality metrics instead of writing a translated file,
+/// so users can compare models/`batch_size`/`threads` empirically rather
+/// than guessing.
+#[tauri::command]
+pub async fn run_benchmark(
+    app: AppHandle,
+    config: TranslatorConfig,
+    workload_path: String,
+) -> Result<BenchmarkReport, String> {
+    let workload_json = std::fs::read_to_string(&workload_path).map_err(|e| e.to_string())?;
+    let workload: Workload = serde_json::from_str(&workload_json).map_err(|e| e.to_string())?;
+
+    let mut results = Vec::new();
+    for job in &workload.jobs {
+        results.push(run_benchmark_job(&app, &config, job).await?);
+    }
+
+    let report = BenchmarkReport { results };
+
+    if let Some(results_file) = &workload.results_file {
+        let mut existing: Vec<BenchmarkResult> = std::fs::read_to_string(results_file)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        existing.extend(report.results.clone());
+        let json = serde_json::to_string_pretty(&existing).map_err(|e| e.to_string())?;
+        std::fs::write(results_file, json).map_err(|e| e.to_string())?;
+    }
+
+    Ok(report)
+}
+
+async fn run_benchmark_job(
     app: &AppHandle,
+    base_config: &TranslatorConfig,
+    job: &BenchmarkJob,
+) -> Result<BenchmarkResult, String> {
+    let content = std::fs::read_to_string(&job.input_sample).map_err(|e| e.to_string())?;
+    let lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
+    let start_idx = if !lines.is_empty() && lines[0].starts_with("0:::") { 1 } else { 0 };
+
+    let work_items: Vec<(usize, String)> = lines
+        .iter()
+        .enumerate()
+        .skip(start_idx)
+        .map(|(i, l)| (i, l.clone()))
+        .collect();
+    let total_items = work_items.len();
+
+    let mut job_config = base_config.clone();
+    job_config.model = job.model.clone();
+    job_config.temperature = job.temperature;
+    job_config.threads = job.threads;
+    job_config.batch_size = job.batch_size;
+    job_config.cache_enabled = false;
+    let config = Arc::new(job_config);
+
+    let num_threads = config.threads.max(1);
+    let chunk_size = (total_items as f64 / num_threads as f64).ceil().max(1.0) as usize;
+    let chunks: Vec<Vec<(usize, String)>> = work_items.chunks(chunk_size).map(|c| c.to_vec()).collect();
+
+    let tm_cache: TmCache = Arc::new(Mutex::new(HashMap::new()));
+    let in_flight: InFlightSet = Arc::new(Mutex::new(std::collections::HashSet::new()));
+    let rate_limiter = Arc::new(RateLimiter::new(config.requests_per_second));
+    let stats = Arc::new(BenchmarkStats::new(job.expected_format.clone()));
+
+    let job_start = std::time::Instant::now();
+    let mut handles = vec![];
+
+    for (thread_id, chunk) in chunks.into_iter().enumerate() {
+        let config = config.clone();
+        let app_handle = app.clone();
+        let tm_cache = tm_cache.clone();
+        let in_flight = in_flight.clone();
+        let rate_limiter = rate_limiter.clone();
+        let stats = stats.clone();
+        let job_name = job.name.clone();
+
+        let handle = tokio::spawn(async move {
+            let thread_id = thread_id + 1;
+            let lines_in_chunk = chunk.len();
+            let client = reqwest::ClientBuilder::new()
+                .timeout(Duration::from_millis(config.request_timeout_ms))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new());
+
+            let thread_start = std::time::Instant::now();
+            let batches = chunk_by_tokens(&chunk, config.batch_size, config.max_prompt_tokens);
+
+            for batch in &batches {
+                let batch_lines: Vec<String> = batch.iter().map(|(_, s)| s.clone()).collect();
+                let _ = call_api_translate(
+                    &client,
+                    &config,
+                    &batch_lines,
+                    &ProgressContext {
+                        app: &app_handle,
+                        thread_id,
+                        current_processed: 0,
+                        total_in_chunk: lines_in_chunk,
+                    },
+                    &tm_cache,
+                    &in_flight,
+                    &rate_limiter,
+                    Some(&stats),
+                ).await;
+            }
+
+            let _ = app_handle.emit("progress", ProgressEvent {
+                thread_id,
+                current: lines_in_chunk,
+                total: lines_in_chunk,
+                message: format!("Benchmark '{}' thread finished.", job_name),
+                append: false,
+            });
+
+            ThreadTiming {
+                thread_id,
+                elapsed_secs: thread_start.elapsed().as_secs_f64(),
+                lines: lines_in_chunk,
+            }
+        });
+        handles.push(handle);
+    }
+
+    let mut thread_timings = Vec::new();
+    for h in handles {
+        if let Ok(timing) = h.await {
+            thread_timings.push(timing);
+        }
+    }
+
+    let elapsed_secs = job_start.elapsed().as_secs_f64();
+    let lines_per_sec = if elapsed_secs > 0.0 { total_items as f64 / elapsed_secs } else { 0.0 };
+
+    Ok(BenchmarkResult {
+        job_name: job.name.clone(),
+        model: job.model.clone(),
+        threads: config.threads,
+        batch_size: config.batch_size,
+        total_lines: total_items,
+        elapsed_secs,
+        lines_per_sec,
+        total_tokens: stats.tokens_used.load(std::sync::atomic::Ordering::SeqCst),
+        missing_ids: stats.missing_ids.load(std::sync::atomic::Ordering::SeqCst),
+        malformed_ids: stats.malformed_ids.load(std::sync::atomic::Ordering::SeqCst),
+        expected_format: job.expected_format.clone(),
+        thread_timings,
+    })
+}
+
+/// Reads a `TranslatorConfig` from a JSON file, for the control socket's
+/// `start <file> <config_path>` form, which lets a cold process (no prior
+/// GUI-initiated run) supply credentials/model settings of its own.
+fn load_config_file(config_path: &str) -> Result<TranslatorConfig, String> {
+    let json = std::fs::read_to_string(config_path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&json).map_err(|e| e.to_string())
+}
+
+/// Starts a background TCP listener so the translator can be driven without
+/// the GUI: scripted bulk jobs, watch-folder setups, or CI. Accepts
+/// line-oriented commands (`start <file>`, `stop`, `status`) and streams
+/// back the same progress data the GUI receives as `ProgressEvent`s.
+///
+/// `start` takes an optional second argument, `start <file> <config_path>`,
+/// pointing at a JSON-serialized `TranslatorConfig`. That config is also
+/// remembered as the state's `last_config`, so a process with no preceding
+/// GUI run (the CI/watch-folder case this socket targets) can still start a
+/// job; a bare `start <file>` still reuses whatever config the GUI (or an
+/// earlier socket command) last ran with.
+#[tauri::command]
+pub async fn start_control_socket(app: AppHandle, bind_addr: String) -> Result<(), String> {
+    let listener = tokio::net::TcpListener::bind(&bind_addr).await.map_err(|e| e.to_string())?;
+
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = match listener.accept().await {
+                Ok(pair) => pair,
+                Err(_) => continue,
+            };
+            tokio::spawn(handle_control_connection(app.clone(), socket));
+        }
+    });
+
+    Ok(())
+}
+
+async fn handle_control_connection(app: AppHandle, socket: tokio::net::TcpStream) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (read_half, write_half) = socket.into_split();
+    let mut lines = BufReader::new(read_half).lines();
+
+    // A channel lets both this command loop and the progress-event listener
+    // (registered per `start`) write to the same socket without fighting
+    // over the write half.
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+    let writer_task = tokio::spawn(async move {
+        let mut write_half = write_half;
+        while let Some(msg) = rx.recv().await {
+            if write_half.write_all(msg.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let line = line.trim().to_string();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("start ") {
+            let mut parts = rest.trim().splitn(2, char::is_whitespace);
+            let file = parts.next().unwrap_or("").trim().to_string();
+            let config_path = parts.next().map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+
+            let state = app.state::<TranslatorState>();
+            let cfg = match config_path {
+                Some(config_path) => match load_config_file(&config_path) {
+                    Ok(cfg) => {
+                        *state.last_config.lock().unwrap() = Some(cfg.clone());
+                        Some(cfg)
+                    }
+                    Err(e) => {
+                        let _ = tx.send(format!(
+                            "error failed to load config {}: {}\n",
+                            config_path, e
+                        ));
+                        continue;
+                    }
+                },
+                None => state.last_config.lock().unwrap().clone(),
+            };
+
+            match cfg {
+                Some(cfg) => {
+                    let tx_progress = tx.clone();
+                    let listener_id = app.listen("progress", move |event| {
+                        if let Ok(evt) = serde_json::from_str::<ProgressEvent>(event.payload()) {
+                            let _ = tx_progress.send(format!(
+                                "progress thread={} current={} total={} message={}\n",
+                                evt.thread_id, evt.current, evt.total, evt.message,
+                            ));
+                        }
+                    });
+
+                    let app_done = app.clone();
+                    let tx_done = tx.clone();
+                    tokio::spawn(async move {
+                        let state = app_done.state::<TranslatorState>();
+                        let result = run_translation_core(app_done.clone(), &state, cfg, file).await;
+                        app_done.unlisten(listener_id);
+                        let _ = tx_done.send(match result {
+                            Ok(()) => "done ok\n".to_string(),
+                            Err(e) => format!("done error={}\n", e),
+                        });
+                    });
+
+                    let _ = tx.send("ok started\n".to_string());
+                }
+                None => {
+                    let _ = tx.send(
+                        "error no config available; start a translation from the GUI at least once, \
+                         or use 'start <file> <config_path>' to supply one\n"
+                            .to_string(),
+                    );
+                }
+            }
+        } else if line == "stop" {
+            let state = app.state::<TranslatorState>();
+            let _ = tx.send(match stop_translation_core(&state) {
+                Ok(()) => "ok stopping\n".to_string(),
+                Err(e) => format!("error {}\n", e),
+            });
+        } else if line == "status" {
+            let state = app.state::<TranslatorState>();
+            let running = *state.running.lock().unwrap();
+            let _ = tx.send(format!("status {}\n", if running { "running" } else { "idle" }));
+        } else {
+            let _ = tx.send(format!("error unknown command: {}\n", line));
+        }
+    }
+
+    drop(tx);
+    let _ = writer_task.await;
+}
+
+/// Bundles the progress-event fields threaded through every batch call, since
+/// `app`, `thread_id`, `current_processed`, and `total_in_chunk` always travel
+/// together.
+struct ProgressContext<'a> {
+    app: &'a AppHandle,
     thread_id: usize,
     current_processed: usize,
     total_in_chunk: usize,
+}
+
+async fn call_api_translate(
+    client: &reqwest::Client,
+    config: &TranslatorConfig,
+    lines: &[String],
+    progress: &ProgressContext<'_>,
+    tm_cache: &TmCache,
+    in_flight: &InFlightSet,
+    rate_limiter: &RateLimiter,
+    stats: Option<&BenchmarkStats>,
 ) -> Vec<String> {
+    let app = progress.app;
+    let thread_id = progress.thread_id;
+    let current_processed = progress.current_processed;
+    let total_in_chunk = progress.total_in_chunk;
+    // Split into TM hits (served straight from the cache) and misses (the
+    // only ones actually worth sending to the API).
+    let mut cache_hits: HashMap<String, String> = HashMap::new();
+    let mut misses: Vec<String> = Vec::new();
+    // Segments this call claimed in `in_flight`; released once their result
+    // (success or failure) lands, so no claim outlives this call.
+    let mut claimed_hashes: Vec<String> = Vec::new();
+    // Lines in *this* batch that repeat a segment this same call already
+    // claimed (e.g. two lines whose source text is both "Yes"): resolved from the
+    // claiming line's own result below, never through the cross-thread
+    // `waiters` path — that path waits on `tm_cache`, which this call hasn't
+    // written yet, so a same-call repeat would spin until it timed out.
+    let mut same_call_duplicates: Vec<(String, String)> = Vec::new();
+
+    if config.cache_enabled {
+        // Segments another thread already claimed this run: wait for that
+        // thread's result to land in the cache instead of re-requesting it.
+        let mut waiters: Vec<(String, String)> = Vec::new();
+        let mut claimed_this_call: HashMap<String, String> = HashMap::new();
+
+        {
+            let cache = tm_cache.lock().unwrap();
+            let mut in_flight = in_flight.lock().unwrap();
+            for line in lines {
+                if let Some((id, text)) = line.split_once(":::") {
+                    let id = id.trim().to_string();
+                    let key = hash_segment(text);
+                    if let Some(translated) = cache.get(&key) {
+                        cache_hits.insert(id, translated.clone());
+                    } else if let Some(primary_id) = claimed_this_call.get(&key) {
+                        same_call_duplicates.push((id, primary_id.clone()));
+                    } else if in_flight.insert(key.clone()) {
+                        claimed_this_call.insert(key.clone(), id.clone());
+                        claimed_hashes.push(key);
+                        misses.push(line.clone());
+                    } else {
+                        waiters.push((id, key));
+                    }
+                } else {
+                    misses.push(line.clone());
+                }
+            }
+        }
+
+        for (id, key) in waiters {
+            for _ in 0..100 {
+                if let Some(translated) = tm_cache.lock().unwrap().get(&key).cloned() {
+                    cache_hits.insert(id.clone(), translated);
+                    break;
+                }
+                if !in_flight.lock().unwrap().contains(&key) {
+                    // The claiming thread finished without a result (it
+                    // failed); fall through and let this line stay
+                    // untranslated rather than wait forever.
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            }
+        }
+    } else {
+        misses = lines.to_vec();
+    }
+
+    if misses.is_empty() {
+        let _ = app.emit("progress", ProgressEvent {
+            thread_id,
+            current: current_processed,
+            total: total_in_chunk,
+            message: format!(
+                "TM cache hit for {} of {} lines; nothing left to send.",
+                cache_hits.len(),
+                lines.len(),
+            ),
+            append: true,
+        });
+        return lines
+            .iter()
+            .map(|line| match line.split_once(":::") {
+                Some((id, _)) => match cache_hits.get(id.trim()) {
+                    Some(trans) => format!("{}:::{}", id.trim(), trans),
+                    None => line.clone(),
+                },
+                None => line.clone(),
+            })
+            .collect();
+    }
+
+    let mut translated_map = request_translations(
+        client, config, &misses, progress, rate_limiter, stats,
+    ).await;
+
+    // Repair pass: the model can drop or merge ID:::Text lines in a large
+    // batch, which otherwise silently falls back to the untranslated
+    // original with no indication. Re-request just the missing IDs.
+    //
+    // A batch that came back completely empty didn't drop a few lines, it
+    // failed outright (bad key, bad request, exhausted retries) — re-issuing
+    // the same full batch through `max_repair_attempts` more rounds just
+    // multiplies a hard failure, so skip repair and let the batch fall back
+    // to untranslated originals.
+    let mut missing: Vec<String> = missing_lines(&misses, &translated_map);
+    let total_failure = translated_map.is_empty();
+    let mut repair_attempt = 0u32;
+    if total_failure && !missing.is_empty() {
+        let _ = app.emit("progress", ProgressEvent {
+            thread_id,
+            current: current_processed,
+            total: total_in_chunk,
+            message: format!(
+                "Batch request failed entirely ({} lines); skipping repair pass.",
+                missing.len(),
+            ),
+            append: true,
+        });
+    } else {
+        while !missing.is_empty() && repair_attempt < config.max_repair_attempts {
+            repair_attempt += 1;
+            let missing_count_before = missing.len();
+
+            let repaired = request_translations(
+                client, config, &missing, progress, rate_limiter, stats,
+            ).await;
+            translated_map.extend(repaired);
+
+            missing = missing_lines(&misses, &translated_map);
+
+            let _ = app.emit("progress", ProgressEvent {
+                thread_id,
+                current: current_processed,
+                total: total_in_chunk,
+                message: format!(
+                    "Repair attempt {}/{}: recovered {}, still missing {}.",
+                    repair_attempt,
+                    config.max_repair_attempts,
+                    missing_count_before - missing.len(),
+                    missing.len(),
+                ),
+                append: true,
+            });
+        }
+    }
+
+    // Resolve same-batch repeats of a claimed segment from the claiming
+    // line's own (possibly still-missing) result, rather than waiting on a
+    // cache write that only happens after this call returns.
+    for (dup_id, primary_id) in &same_call_duplicates {
+        if let Some(trans) = translated_map.get(primary_id).cloned() {
+            translated_map.insert(dup_id.clone(), trans);
+        }
+    }
+
+    if let Some(stats) = stats {
+        let missing_duplicates = same_call_duplicates
+            .iter()
+            .filter(|(dup_id, _)| !translated_map.contains_key(dup_id))
+            .count();
+        stats.missing_ids.fetch_add(
+            missing.len() + missing_duplicates,
+            std::sync::atomic::Ordering::SeqCst,
+        );
+    }
+
+    if config.cache_enabled {
+        let mut cache = tm_cache.lock().unwrap();
+        for line in lines {
+            if let Some((id, text)) = line.split_once(":::") {
+                if let Some(trans) = translated_map.get(id.trim()) {
+                    cache.insert(hash_segment(text), trans.clone());
+                }
+            }
+        }
+    }
+
+    if !claimed_hashes.is_empty() {
+        let mut in_flight = in_flight.lock().unwrap();
+        for key in &claimed_hashes {
+            in_flight.remove(key);
+        }
+    }
+
+    let mut result_lines = Vec::new();
+    for line in lines {
+        if let Some((id, _)) = line.split_once(":::") {
+            let id = id.trim();
+            if let Some(trans) = translated_map.get(id).or_else(|| cache_hits.get(id)) {
+                result_lines.push(format!("{}:::{}", id, trans));
+            } else {
+                result_lines.push(line.clone());
+            }
+        } else {
+            result_lines.push(line.clone());
+        }
+    }
+
+    result_lines
+}
+
+/// Returns the subset of `lines` whose ID has no entry in `translated_map`,
+/// i.e. the ones the model dropped, merged, or reordered out of the reply.
+fn missing_lines(lines: &[String], translated_map: &HashMap<String, String>) -> Vec<String> {
+    lines
+        .iter()
+        .filter(|line| match line.split_once(":::") {
+            Some((id, _)) => !translated_map.contains_key(id.trim()),
+            None => false,
+        })
+        .cloned()
+        .collect()
+}
+
+/// Sends `lines` to the chat-completions endpoint with retry/backoff and a
+/// shared rate limit, and parses the reply into an `id -> translated text`
+/// map. IDs the model didn't return simply aren't present in the result.
+async fn request_translations(
+    client: &reqwest::Client,
+    config: &TranslatorConfig,
+    lines: &[String],
+    progress: &ProgressContext<'_>,
+    rate_limiter: &RateLimiter,
+    stats: Option<&BenchmarkStats>,
+) -> HashMap<String, String> {
+    let app = progress.app;
+    let thread_id = progress.thread_id;
+    let current_processed = progress.current_processed;
+    let total_in_chunk = progress.total_in_chunk;
+    let mut translated_map = HashMap::new();
+    if lines.is_empty() {
+        return translated_map;
+    }
+
     let prompt = lines.join("\n") + "\n\nREMINDER: Format 'ID:::TranslatedText'.";
-    
+
     let url = format!("{}/chat/completions", config.base_url.trim_end_matches('/'));
-    
+
     let mut payload = serde_json::json!({
         "model": config.model,
         "messages": [
@@ -265,104 +1245,132 @@ async fn call_api_translate(
         payload["top_k"] = serde_json::json!(config.top_k);
     }
 
-    let mut result_lines = lines.to_vec(); // Default to original on failure
+    let max_attempts = config.max_retries.saturating_add(1);
+    let mut attempt = 0u32;
 
-    let resp_res = client.post(&url)
-        .header("Authorization", format!("Bearer {}", config.api_key))
-        .json(&payload)
-        .send()
-        .await;
+    let resp = loop {
+        rate_limiter.acquire().await;
+
+        let resp_res = client.post(&url)
+            .header("Authorization", format!("Bearer {}", config.api_key))
+            .json(&payload)
+            .send()
+            .await;
+
+        attempt += 1;
+
+        match resp_res {
+            Ok(resp) if resp.status().is_success() => break Some(resp),
+            Ok(resp) => {
+                let status = resp.status();
+                let retryable = status.as_u16() == 429 || status.is_server_error();
+                let retry_after = resp
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<f64>().ok());
 
-    match resp_res {
-        Ok(resp) => {
-            if !resp.status().is_success() {
                 let _ = app.emit("progress", ProgressEvent {
                     thread_id,
                     current: current_processed,
                     total: total_in_chunk,
-                    message: format!("API Error: {}", resp.status()),
+                    message: format!("API Error: {} (attempt {}/{})", status, attempt, max_attempts),
                     append: true,
                 });
-                return result_lines;
+
+                if !retryable || attempt >= max_attempts {
+                    break None;
+                }
+                tokio::time::sleep(Duration::from_secs_f64(
+                    retry_after.unwrap_or_else(|| backoff_delay_secs(attempt)),
+                )).await;
             }
+            Err(e) => {
+                let retryable = e.is_timeout() || e.is_connect();
 
-            let mut full_content = String::new();
+                let _ = app.emit("progress", ProgressEvent {
+                    thread_id,
+                    current: current_processed,
+                    total: total_in_chunk,
+                    message: format!("Exception: {} (attempt {}/{})", e, attempt, max_attempts),
+                    append: true,
+                });
 
-            if config.stream {
-                use futures_util::StreamExt;
-                let mut stream = resp.bytes_stream();
-                
-                while let Some(item) = stream.next().await {
-                    if let Ok(chunk) = item {
-                        let s = String::from_utf8_lossy(&chunk);
-                        for line in s.lines() {
-                            let line = line.trim();
-                            if line.starts_with("data: ") {
-                                let data = &line[6..];
-                                if data == "[DONE]" { break; }
-                                if let Ok(json) = serde_json::from_str::<serde_json::Value>(data) {
-                                    if let Some(content) = json["choices"][0]["delta"]["content"].as_str() {
-                                        full_content.push_str(content);
-                                        // Emit log update (optional, might be too spammy)
-                                        // let _ = app.emit("progress", ProgressEvent {
-                                        //     thread_id,
-                                        //     current: current_processed,
-                                        //     total: total_in_chunk,
-                                        //     message: content.to_string(),
-                                        //     append: true,
-                                        // });
-                                    }
-                                }
+                if !retryable || attempt >= max_attempts {
+                    break None;
+                }
+                tokio::time::sleep(Duration::from_secs_f64(backoff_delay_secs(attempt))).await;
+            }
+        }
+    };
+
+    let resp = match resp {
+        Some(resp) => resp,
+        None => return translated_map,
+    };
+
+    let mut full_content = String::new();
+
+    if config.stream {
+        use futures_util::StreamExt;
+        let mut stream = resp.bytes_stream();
+
+        while let Some(item) = stream.next().await {
+            if let Ok(chunk) = item {
+                let s = String::from_utf8_lossy(&chunk);
+                for line in s.lines() {
+                    let line = line.trim();
+                    if line.starts_with("data: ") {
+                        let data = &line[6..];
+                        if data == "[DONE]" { break; }
+                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(data) {
+                            if let Some(content) = json["choices"][0]["delta"]["content"].as_str() {
+                                full_content.push_str(content);
                             }
                         }
                     }
                 }
-            } else {
-                if let Ok(json) = resp.json::<serde_json::Value>().await {
-                    if let Some(content) = json["choices"][0]["message"]["content"].as_str() {
-                        full_content = content.to_string();
-                    }
-                }
             }
-
-            // Parse results
-            let translated_lines: Vec<&str> = full_content.trim().split('\n').collect();
-            let mut translated_map = std::collections::HashMap::new();
-            
-            for line in translated_lines {
-                if let Some((id, text)) = line.split_once(":::") {
-                    translated_map.insert(id.trim().to_string(), text.trim().to_string());
-                } else {
-                    // Try regex fallback if needed, or simple heuristic
-                    // For now, simple split
-                }
+        }
+    } else {
+        if let Ok(json) = resp.json::<serde_json::Value>().await {
+            if let Some(content) = json["choices"][0]["message"]["content"].as_str() {
+                full_content = content.to_string();
             }
+            if let (Some(stats), Some(total_tokens)) = (stats, json["usage"]["total_tokens"].as_u64()) {
+                stats.tokens_used.fetch_add(total_tokens, std::sync::atomic::Ordering::SeqCst);
+            }
+        }
+    }
 
-            let mut new_results = Vec::new();
-            for line in lines {
-                if let Some((id, _)) = line.split_once(":::") {
-                    let id = id.trim();
-                    if let Some(trans) = translated_map.get(id) {
-                        new_results.push(format!("{}:::{}", id, trans));
-                    } else {
-                        new_results.push(line.clone());
-                    }
-                } else {
-                    new_results.push(line.clone());
+    let mut malformed = 0usize;
+    for line in full_content.trim().split('\n') {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some((id, text)) = line.split_once(":::") {
+            translated_map.insert(id.trim().to_string(), text.trim().to_string());
+            if let Some(stats) = stats {
+                if !line_matches_format(line, &stats.expected_format) {
+                    malformed += 1;
                 }
             }
-            result_lines = new_results;
-        }
-        Err(e) => {
-            let _ = app.emit("progress", ProgressEvent {
-                thread_id,
-                current: current_processed,
-                total: total_in_chunk,
-                message: format!("Exception: {}", e),
-                append: true,
-            });
+        } else {
+            malformed += 1;
         }
     }
+    if let Some(stats) = stats {
+        stats.malformed_ids.fetch_add(malformed, std::sync::atomic::Ordering::SeqCst);
+    }
 
-    result_lines
+    translated_map
+}
+
+/// Exponential backoff with jitter: doubles each attempt, capped, plus up to
+/// 25% random jitter so many threads retrying together don't re-collide.
+fn backoff_delay_secs(attempt: u32) -> f64 {
+    let base = 0.5_f64 * 2f64.powi(attempt as i32 - 1);
+    let capped = base.min(30.0);
+    let jitter: f64 = rand::thread_rng().gen_range(0.0..=(capped * 0.25).max(0.001));
+    capped + jitter
 }